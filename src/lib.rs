@@ -2,6 +2,7 @@
 extern crate failure;
 
 use failure::Error;
+use std::io;
 use std::io::prelude::*;
 use std::io::SeekFrom;
 use std::fs::File;
@@ -18,27 +19,75 @@ enum MP3DurationError {
     InvalidBitrate { bitrate: u8, },
     #[fail(display = "Invalid sampling rate bits: {}", sampling_rate)]
     InvalidSamplingRate { sampling_rate: u8, },
+    #[fail(display = "Invalid emphasis bits (reserved value)")]
+    ForbiddenEmphasis,
     #[fail(display = "Unexpected frame, header: {}", header)]
     UnexpectedFrame {
         header: u32,
     }
 }
 
-#[derive(Clone, Copy, Debug)]
-enum Version {
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Version {
     Mpeg1,
     Mpeg2,
     Mpeg25,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
-enum Layer {
+pub enum Layer {
     NotDefined,
     Layer1,
     Layer2,
     Layer3,
 }
 
+/// The channel mode a frame was encoded with.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ChannelMode {
+    Stereo,
+    JointStereo,
+    DualChannel,
+    Mono,
+}
+
+/// Whether a file was encoded at a constant or variable bitrate, as
+/// observed across its frames.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BitrateMode {
+    ConstantBitrate,
+    VariableBitrate,
+}
+
+/// Metadata gathered while scanning an MP3 file, in addition to its
+/// duration. Produced by [`from_file_ext`] and [`from_path_ext`].
+#[derive(Clone, Copy, Debug)]
+pub struct Mp3Metadata {
+    /// Total playback duration.
+    pub duration: Duration,
+    /// MPEG version of the first audio frame.
+    pub version: Version,
+    /// MPEG layer of the first audio frame.
+    pub layer: Layer,
+    /// Channel mode of the first audio frame.
+    pub channel_mode: ChannelMode,
+    /// Sampling rate, in Hz, of the first audio frame.
+    pub sampling_rate: u32,
+    /// Whether the observed bitrate was constant or variable across frames.
+    /// When the first frame carries a Xing/Info or VBRI tag, this is taken
+    /// from the tag instead: `Info` means constant, `Xing` and VBRI mean
+    /// variable.
+    pub bitrate_mode: BitrateMode,
+    /// Average bitrate across the file, in bits per second. When the first
+    /// frame carries a Xing/Info tag without a byte count, this is the first
+    /// frame's bitrate, not an average.
+    pub average_bitrate: u32,
+    /// Total number of MPEG frames.
+    pub frame_count: u32,
+    /// Total number of samples (`frame_count * samples_per_frame`).
+    pub sample_count: u64,
+}
+
 static BIT_RATES: [[[u32; 16]; 4]; 3] = [[
         [0;16],
         [0, 32, 64, 96, 128, 160, 192, 224, 256, 288, 320, 352, 384, 416, 448, 0], // Mpeg1 Layer1
@@ -95,6 +144,87 @@ fn get_samples_per_frame(version: Version, layer: Layer) -> Result<u32, Error> {
     Ok(SAMPLES_PER_FRAME[version as usize][layer as usize])
 }
 
+// What a Xing/Info or VBRI tag told us about the stream.
+struct VbrTagInfo {
+    frame_count: u32,
+    // Total stream size in bytes, when the tag bothered to store it; lets
+    // us derive a real average bitrate instead of reusing the first
+    // frame's nominal one.
+    byte_count: Option<u32>,
+    // LAME writes the identical tag format under "Info" instead of "Xing"
+    // specifically to mark a CBR/ABR-encoded stream rather than a VBR one;
+    // VBRI (Fraunhofer) is only ever written for VBR.
+    is_vbr: bool,
+}
+
+fn read_be_u32(bytes: &[u8]) -> u32 {
+    (bytes[0] as u32) << 24 | (bytes[1] as u32) << 16 | (bytes[2] as u32) << 8 | bytes[3] as u32
+}
+
+// Most VBR encoders write a Xing/Info or VBRI tag into the first frame
+// (right after its side information, or at a fixed offset for VBRI),
+// storing the total frame count directly so we
+// don't have to walk every frame to know the duration. `file` must be
+// positioned right after the frame header on entry; regardless of whether
+// a tag is found, it is restored to that position before returning.
+fn read_vbr_frame_count<T>(file: &mut T, version: Version, channel_mode: u8) -> Result<Option<VbrTagInfo>, Error>
+    where T: Read + Seek
+{
+    let start = file.seek(SeekFrom::Current(0))?;
+
+    // VBRI tag: fixed offset, independent of version/channel mode.
+    file.seek(SeekFrom::Start(start + 32))?;
+    let mut tag = [0; 4];
+    if file.read_exact(&mut tag).is_ok() && &tag == b"VBRI" {
+        let mut header = [0; 10];
+        file.read_exact(&mut header)?;
+        let mut frames = [0; 4];
+        file.read_exact(&mut frames)?;
+        file.seek(SeekFrom::Start(start))?;
+        return Ok(Some(VbrTagInfo {
+            frame_count: read_be_u32(&frames),
+            byte_count: Some(read_be_u32(&header[6..10])),
+            is_vbr: true,
+        }));
+    }
+
+    // Xing/Info tag: sits right after the side information, whose size
+    // depends on the MPEG version and the channel mode (mono vs not).
+    let side_info_size = match (version, channel_mode) {
+        (Version::Mpeg1, 3) => 17, // mono
+        (Version::Mpeg1, _) => 32, // stereo / joint stereo / dual channel
+        (_, 3) => 9,               // MPEG2/2.5 mono
+        (_, _) => 17,              // MPEG2/2.5 stereo
+    };
+    file.seek(SeekFrom::Start(start + side_info_size))?;
+    if file.read_exact(&mut tag).is_ok() && (&tag == b"Xing" || &tag == b"Info") {
+        let is_vbr = &tag == b"Xing";
+        let mut flags = [0; 4];
+        file.read_exact(&mut flags)?;
+        let flags = read_be_u32(&flags);
+        if 0 != (flags & 0x1) {
+            let mut frames = [0; 4];
+            file.read_exact(&mut frames)?;
+            let byte_count = if 0 != (flags & 0x2) {
+                let mut bytes = [0; 4];
+                file.read_exact(&mut bytes)?;
+                Some(read_be_u32(&bytes))
+            } else {
+                None
+            };
+            file.seek(SeekFrom::Start(start))?;
+            return Ok(Some(VbrTagInfo {
+                frame_count: read_be_u32(&frames),
+                byte_count,
+                is_vbr,
+            }));
+        }
+    }
+
+    file.seek(SeekFrom::Start(start))?;
+    Ok(None)
+}
+
 /// Measures the duration of a file.
 ///
 /// # Examples
@@ -111,10 +241,248 @@ fn get_samples_per_frame(version: Version, layer: Layer) -> Result<u32, Error> {
 /// ```
 pub fn from_file<T>(file: &mut T) -> Result<Duration, Error>
     where T: Read + Seek
+{
+    Ok(scan(file, false)?.duration)
+}
+
+/// Measures the duration of a file along with additional metadata (bitrate
+/// mode, average bitrate, sampling rate, channel mode, MPEG version/layer,
+/// and total frame/sample count).
+///
+/// # Examples
+///
+/// ```
+/// use std::path::Path;
+/// use std::fs::File;
+/// use mp3_duration;
+///
+/// let path = Path::new("test/source.mp3");
+/// let mut file = File::open(path).unwrap();
+/// let metadata = mp3_duration::from_file_ext(&mut file).unwrap();
+/// println!("File duration: {:?}", metadata.duration);
+/// ```
+pub fn from_file_ext<T>(file: &mut T) -> Result<Mp3Metadata, Error>
+    where T: Read + Seek
+{
+    scan(file, false)
+}
+
+/// Measures the duration of a file, recovering from corrupt or unexpected
+/// data instead of aborting.
+///
+/// Some files carry garbage in the audio region (embedded album art, a
+/// truncated frame, stray APE data that isn't recognized as a trailing tag)
+/// that would otherwise make [`from_file`] fail with
+/// `MP3DurationError::UnexpectedFrame`. This variant slides forward byte by
+/// byte looking for the next valid frame instead of giving up, at the cost
+/// of being slightly more permissive about what it accepts as audio.
+///
+/// # Examples
+///
+/// ```
+/// use std::path::Path;
+/// use std::fs::File;
+/// use mp3_duration;
+///
+/// let path = Path::new("test/source.mp3");
+/// let mut file = File::open(path).unwrap();
+/// let duration = mp3_duration::from_file_lossy(&mut file).unwrap();
+/// println!("File duration: {:?}", duration);
+/// ```
+pub fn from_file_lossy<T>(file: &mut T) -> Result<Duration, Error>
+    where T: Read + Seek
+{
+    Ok(scan(file, true)?.duration)
+}
+
+fn channel_mode_from_bits(encoded_channel_mode: u32) -> ChannelMode {
+    match encoded_channel_mode {
+        0 => ChannelMode::Stereo,
+        1 => ChannelMode::JointStereo,
+        2 => ChannelMode::DualChannel,
+        3 => ChannelMode::Mono,
+        _ => unreachable!(),
+    }
+}
+
+// How `skip_tag` advances past a tag body it has decided not to inspect.
+// A `Read + Seek` source can jump directly; a forward-only one has to read
+// and discard. `scan` and `scan_reader` share their tag parsing by passing
+// one of these to `skip_tag`; each still skips frame bodies itself, with
+// `seek` or `skip` respectively.
+trait Advance<R> {
+    fn advance(reader: &mut R, bytes: u64) -> Result<(), Error>;
+}
+
+struct SeekAdvance;
+impl<R: Read + Seek> Advance<R> for SeekAdvance {
+    fn advance(reader: &mut R, bytes: u64) -> Result<(), Error> {
+        reader.seek(SeekFrom::Current(bytes as i64))?;
+        Ok(())
+    }
+}
+
+struct SkipAdvance;
+impl<R: Read> Advance<R> for SkipAdvance {
+    fn advance(reader: &mut R, bytes: u64) -> Result<(), Error> {
+        skip(reader, bytes)
+    }
+}
+
+// Recognizes `buffer` as the start of one of the trailing/leading tag
+// formats this crate skips over (ID3v1, ID3v2, APEv2, Lyrics3v2) and, if
+// so, advances `reader` past it. Returns `Ok(true)` when a tag was fully
+// skipped, in which case the caller should read a fresh 4-byte window and
+// keep looping. Returns `Ok(false)` when nothing matched; `buffer` may
+// have been refreshed with leftover lookahead bytes for the caller to
+// decode as a frame header instead, since neither `scan` nor
+// `scan_reader` can always rewind past a false-positive match.
+fn skip_tag<R, A>(reader: &mut R, buffer: &mut [u8; 4]) -> Result<bool, Error>
+    where R: Read, A: Advance<R>
+{
+    // ID3v1 frame
+    let is_id3v1 = buffer[0] == 'T' as u8 && buffer[1] == 'A' as u8 && buffer[2] == 'G' as u8;
+    if is_id3v1 {
+        A::advance(reader, 124)?; // 4 bytes already read
+        return Ok(true);
+    }
+
+    // ID3v2 frame
+    let is_id3v2 = buffer[0] == 'I' as u8 && buffer[1] == 'D' as u8 && buffer[2] == '3' as u8;
+    if is_id3v2 {
+        let mut id3v2 = [0; 6]; // 4 bytes already read
+        reader.read_exact(&mut id3v2)?;
+        let flags = id3v2[1];
+        let footer_size = if 0 != (flags & 0b00010000) { 10 } else { 0 };
+        let tag_size = (id3v2[5] as u32) | ((id3v2[4] as u32) << 7) |
+                       ((id3v2[3] as u32) << 14) |
+                       ((id3v2[2] as u32) << 21);
+        A::advance(reader, tag_size as u64 + footer_size as u64)?;
+        return Ok(true);
+    }
+
+    // APEv2 tag (commonly appended by ReplayGain tools)
+    let is_apev2 = buffer[0] == 'A' as u8 && buffer[1] == 'P' as u8 &&
+                   buffer[2] == 'E' as u8 && buffer[3] == 'T' as u8;
+    if is_apev2 {
+        let mut rest_of_preamble = [0; 4]; // "APET" already read; confirm "AGEX"
+        reader.read_exact(&mut rest_of_preamble)?;
+        if &rest_of_preamble == b"AGEX" {
+            let mut header = [0; 24]; // remaining fields of the 32-byte header
+            reader.read_exact(&mut header)?;
+            let tag_size = (header[4] as u32) | (header[5] as u32) << 8 |
+                           (header[6] as u32) << 16 | (header[7] as u32) << 24;
+            // `tag_size` already covers the tag body and its 32-byte footer.
+            A::advance(reader, tag_size as u64)?;
+            return Ok(true);
+        }
+        // Not actually APEv2: treat the lookahead as the start of
+        // whatever comes next instead of silently discarding it.
+        *buffer = rest_of_preamble;
+    }
+
+    // Lyrics3v2 tag (legacy lyrics format, predates APEv2)
+    let is_lyrics3 = buffer[0] == 'L' as u8 && buffer[1] == 'Y' as u8 &&
+                     buffer[2] == 'R' as u8 && buffer[3] == 'I' as u8;
+    if is_lyrics3 {
+        let mut rest = [0; 7]; // "LYRI" already read; confirm "CSBEGIN"
+        reader.read_exact(&mut rest)?;
+        if &rest == b"CSBEGIN" {
+            let mut size = [0; 6]; // ASCII decimal size of the content up to "LYRICS200"
+            reader.read_exact(&mut size)?;
+            let tag_size: u64 = std::str::from_utf8(&size)?.trim().parse()?;
+            A::advance(reader, tag_size)?;
+            let mut end_marker = [0; 9];
+            reader.read_exact(&mut end_marker)?;
+            if &end_marker != b"LYRICS200" {
+                bail!(MP3DurationError::UnexpectedFrame{ header: read_be_u32(&end_marker[0..4]) });
+            }
+            return Ok(true);
+        }
+        // Not actually Lyrics3v2: reuse the last 4 lookahead bytes as the
+        // next window, as above; the 3 bytes before them are dropped.
+        buffer.copy_from_slice(&rest[3..]);
+    }
+
+    Ok(false)
+}
+
+// Everything `scan`/`scan_reader` need from a validated MPEG frame header;
+// decoding it is the part both scanners used to duplicate byte-for-byte.
+struct FrameInfo {
+    version: Version,
+    layer: Layer,
+    channel_mode: ChannelMode,
+    encoded_channel_mode: u8,
+    sampling_rate: u32,
+    bitrate: u32,
+    num_samples: u32,
+    frame_duration: u64,
+    frame_length: u32,
+}
+
+fn decode_frame_header(header: u32) -> Result<FrameInfo, Error> {
+    let version = match (header >> 19) & 0b11 {
+        0 => Version::Mpeg25,
+        1 => bail!(MP3DurationError::ForbiddenVersion),
+        2 => Version::Mpeg2,
+        3 => Version::Mpeg1,
+        _ => unreachable!(),
+    };
+
+    let layer = match (header >> 17) & 0b11 {
+        0 => Layer::NotDefined,
+        1 => Layer::Layer3,
+        2 => Layer::Layer2,
+        3 => Layer::Layer1,
+        _ => unreachable!(),
+    };
+
+    if header & 0b11 == 0b10 {
+        bail!(MP3DurationError::ForbiddenEmphasis);
+    }
+
+    let encoded_channel_mode = ((header >> 6) & 0b11) as u8;
+    let channel_mode = channel_mode_from_bits(encoded_channel_mode as u32);
+    let encoded_bitrate = (header >> 12) & 0b1111;
+    let encoded_sampling_rate = (header >> 10) & 0b11;
+    let padding = if 0 != ((header >> 9) & 1) { 1 } else { 0 };
+    let bitrate = get_bitrate(version, layer, encoded_bitrate as u8)?;
+    let sampling_rate = get_sampling_rate(version, encoded_sampling_rate as u8)?;
+    let num_samples = get_samples_per_frame(version, layer)?;
+    let frame_duration = (num_samples as u64 * 1_000_000_000) / (sampling_rate as u64);
+    let frame_length = if layer == Layer::Layer1 {
+        (12 * bitrate / sampling_rate + padding) * 4 - 4
+    } else {
+        num_samples / 8 * bitrate / sampling_rate + padding - 4
+    };
+
+    Ok(FrameInfo {
+        version,
+        layer,
+        channel_mode,
+        encoded_channel_mode,
+        sampling_rate,
+        bitrate,
+        num_samples,
+        frame_duration,
+        frame_length,
+    })
+}
+
+fn scan<T>(file: &mut T, lossy: bool) -> Result<Mp3Metadata, Error>
+    where T: Read + Seek
 {
     let mut buffer = [0; 4];
 
     let mut duration = Duration::from_secs(0);
+    let mut is_first_frame = true;
+    let mut frame_count: u32 = 0;
+    let mut sample_count: u64 = 0;
+    let mut sum_bits: u64 = 0;
+    let mut min_bitrate: Option<u32> = None;
+    let mut max_bitrate: Option<u32> = None;
+    let mut first_frame_info: Option<(Version, Layer, ChannelMode, u32)> = None;
     loop {
         match file.read_exact(&mut buffer[..]) {
             Ok(_) => (),
@@ -126,24 +494,7 @@ pub fn from_file<T>(file: &mut T) -> Result<Duration, Error>
             }
         };
 
-        // ID3v1 frame
-        let is_id3v1 = buffer[0] == 'T' as u8 && buffer[1] == 'A' as u8 && buffer[2] == 'G' as u8;
-        if is_id3v1 {
-            file.seek(SeekFrom::Current(124))?; // 4 bytes already read
-            continue;
-        }
-
-        // ID3v2 frame
-        let is_id3v2 = buffer[0] == 'I' as u8 && buffer[1] == 'D' as u8 && buffer[2] == '3' as u8;
-        if is_id3v2 {
-            let mut id3v2 = [0; 6]; // 4 bytes already read
-            file.read_exact(&mut id3v2)?;
-            let flags = id3v2[1];
-            let footer_size = if 0 != (flags & 0b00010000) { 10 } else { 0 };
-            let tag_size = (id3v2[5] as u32) | ((id3v2[4] as u32) << 7) |
-                           ((id3v2[3] as u32) << 14) |
-                           ((id3v2[2] as u32) << 21);
-            file.seek(SeekFrom::Current(tag_size as i64 + footer_size))?;
+        if skip_tag::<T, SeekAdvance>(file, &mut buffer)? {
             continue;
         }
 
@@ -152,41 +503,145 @@ pub fn from_file<T>(file: &mut T) -> Result<Duration, Error>
                      (buffer[2] as u32) << 8 | buffer[3] as u32;
         let is_mp3 = header >> 21 == 0x7FF;
         if is_mp3 {
+            let info = decode_frame_header(header)?;
 
-            let version = match (header >> 19) & 0b11 {
-                0 => Version::Mpeg25,
-                1 => bail!(MP3DurationError::ForbiddenVersion),
-                2 => Version::Mpeg2,
-                3 => Version::Mpeg1,
-                _ => unreachable!(),
-            };
+            if first_frame_info.is_none() {
+                first_frame_info = Some((info.version, info.layer, info.channel_mode, info.sampling_rate));
+            }
 
-            let layer = match (header >> 17) & 0b11 {
-                0 => Layer::NotDefined,
-                1 => Layer::Layer3,
-                2 => Layer::Layer2,
-                3 => Layer::Layer1,
-                _ => unreachable!(),
-            };
+            if is_first_frame {
+                is_first_frame = false;
+                if let Some(vbr_tag) = read_vbr_frame_count(file, info.version, info.encoded_channel_mode)? {
+                    let total_duration = info.frame_duration * vbr_tag.frame_count as u64;
+                    duration = duration + Duration::new(total_duration / 1_000_000_000,
+                                                          (total_duration % 1_000_000_000) as u32);
+                    let average_bitrate = vbr_tag.byte_count.map(|bytes| {
+                        (bytes as u64 * 8 * 1_000_000_000 / total_duration.max(1)) as u32
+                    }).unwrap_or(info.bitrate);
+                    let bitrate_mode = if vbr_tag.is_vbr {
+                        BitrateMode::VariableBitrate
+                    } else {
+                        BitrateMode::ConstantBitrate
+                    };
+                    return Ok(Mp3Metadata {
+                        duration,
+                        version: info.version,
+                        layer: info.layer,
+                        channel_mode: info.channel_mode,
+                        sampling_rate: info.sampling_rate,
+                        bitrate_mode,
+                        average_bitrate,
+                        frame_count: vbr_tag.frame_count,
+                        sample_count: vbr_tag.frame_count as u64 * info.num_samples as u64,
+                    });
+                }
+            }
+
+            frame_count += 1;
+            sample_count += info.num_samples as u64;
+            sum_bits += (info.bitrate as u64 * info.frame_duration) / 1_000_000_000;
+            min_bitrate = Some(min_bitrate.map_or(info.bitrate, |b| b.min(info.bitrate)));
+            max_bitrate = Some(max_bitrate.map_or(info.bitrate, |b| b.max(info.bitrate)));
 
-            let encoded_bitrate = (header >> 12) & 0b1111;
-            let encoded_sampling_rate = (header >> 10) & 0b11;
-            let padding = if 0 != ((header >> 9) & 1) { 1 } else { 0 };
-            let bitrate = get_bitrate(version, layer, encoded_bitrate as u8)?;
-            let sampling_rate = get_sampling_rate(version, encoded_sampling_rate as u8)?;
-            let num_samples = get_samples_per_frame(version, layer)?;
-            let frame_duration = (num_samples as u64 * 1_000_000_000) / (sampling_rate as u64);
-            let frame_length = num_samples / 8 * bitrate / sampling_rate + padding - 4;
-
-            file.seek(SeekFrom::Current(frame_length as i64))?;
-            duration = duration + Duration::new(0, frame_duration as u32);
+            file.seek(SeekFrom::Current(info.frame_length as i64))?;
+            duration = duration + Duration::new(0, info.frame_duration as u32);
             continue;
         }
 
+        if lossy && try_resync(file, buffer)? {
+            continue;
+        }
+
+        if lossy {
+            break;
+        }
         bail!(MP3DurationError::UnexpectedFrame{ header: header });
     }
 
-    Ok(duration)
+    let (version, layer, channel_mode, sampling_rate) = first_frame_info
+        .unwrap_or((Version::Mpeg1, Layer::Layer3, ChannelMode::Stereo, 0));
+    let bitrate_mode = if min_bitrate == max_bitrate {
+        BitrateMode::ConstantBitrate
+    } else {
+        BitrateMode::VariableBitrate
+    };
+    let average_bitrate = if duration.as_secs() > 0 || duration.subsec_nanos() > 0 {
+        (sum_bits * 1_000_000_000 / (duration.as_secs() * 1_000_000_000 + duration.subsec_nanos() as u64)) as u32
+    } else {
+        0
+    };
+
+    Ok(Mp3Metadata {
+        duration,
+        version,
+        layer,
+        channel_mode,
+        sampling_rate,
+        bitrate_mode,
+        average_bitrate,
+        frame_count,
+        sample_count,
+    })
+}
+
+// The "stable" header bits that must agree between two consecutive frames
+// for a resync candidate to be accepted: sync word, version, layer,
+// sampling rate, channel mode, the copyright and original bits, and
+// emphasis. Bitrate, padding, the protection bit, the private bit and the
+// mode extension are allowed to differ from frame to frame.
+const STABLE_HEADER_MASK: u32 = 0xFFFE0CCF;
+
+// Used only while resyncing: decodes just enough of a candidate header to
+// compute its frame length, returning `None` instead of bailing when any
+// field is invalid so the caller can keep sliding forward.
+fn try_decode_frame_length(header: u32) -> Option<u32> {
+    if header >> 21 != 0x7FF {
+        return None;
+    }
+    decode_frame_header(header).ok().map(|info| info.frame_length)
+}
+
+// On an unexpected word, slide forward one byte at a time looking for the
+// sync pattern, starting from the very next byte after the one already
+// rejected (not after the whole 4-byte word) so a stray single byte before
+// a real frame doesn't make us overshoot it. A candidate is only accepted
+// once the frame that follows it also carries a header whose stable bits
+// (`STABLE_HEADER_MASK`) match, to avoid locking onto random data. Leaves
+// `file` positioned at the start of the accepted candidate header, ready
+// to be read again by the caller.
+fn try_resync<T>(file: &mut T, mut window: [u8; 4]) -> Result<bool, Error>
+    where T: Read + Seek
+{
+    loop {
+        window[0] = window[1];
+        window[1] = window[2];
+        window[2] = window[3];
+        match file.read_exact(&mut window[3..4]) {
+            Ok(_) => (),
+            Err(ref e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(false),
+            Err(e) => bail!(e),
+        }
+        let candidate_offset = file.seek(SeekFrom::Current(0))? - 4;
+
+        let header = (window[0] as u32) << 24 | (window[1] as u32) << 16 |
+                     (window[2] as u32) << 8 | window[3] as u32;
+
+        if let Some(frame_length) = try_decode_frame_length(header) {
+            file.seek(SeekFrom::Start(candidate_offset + 4 + frame_length as u64))?;
+            let mut next = [0; 4];
+            let validated = file.read_exact(&mut next).is_ok() && {
+                let next_header = (next[0] as u32) << 24 | (next[1] as u32) << 16 |
+                                   (next[2] as u32) << 8 | next[3] as u32;
+                next_header & STABLE_HEADER_MASK == header & STABLE_HEADER_MASK
+            };
+            if validated {
+                file.seek(SeekFrom::Start(candidate_offset))?;
+                return Ok(true);
+            }
+            // Resume the byte-by-byte slide right after the rejected window.
+            file.seek(SeekFrom::Start(candidate_offset + 4))?;
+        }
+    }
 }
 
 /// Measures the duration of a file.
@@ -208,6 +663,436 @@ pub fn from_path<P>(path: P) -> Result<Duration, Error>
     from_file(&mut file)
 }
 
+/// Measures the duration of a file along with additional metadata. See
+/// [`from_file_ext`] for details.
+///
+/// # Examples
+///
+/// ```
+/// use std::path::Path;
+/// use mp3_duration;
+///
+/// let path = Path::new("test/source.mp3");
+/// let metadata = mp3_duration::from_path_ext(&path).unwrap();
+/// println!("File duration: {:?}", metadata.duration);
+/// ```
+pub fn from_path_ext<P>(path: P) -> Result<Mp3Metadata, Error>
+    where P: AsRef<Path>
+{
+    let mut file = File::open(path)?;
+    from_file_ext(&mut file)
+}
+
+/// Measures the duration of a file, recovering from corrupt or unexpected
+/// data instead of aborting. See [`from_file_lossy`] for details.
+///
+/// # Examples
+///
+/// ```
+/// use std::path::Path;
+/// use mp3_duration;
+///
+/// let path = Path::new("test/source.mp3");
+/// let duration = mp3_duration::from_path_lossy(&path).unwrap();
+/// println!("File duration: {:?}", duration);
+/// ```
+pub fn from_path_lossy<P>(path: P) -> Result<Duration, Error>
+    where P: AsRef<Path>
+{
+    let mut file = File::open(path)?;
+    from_file_lossy(&mut file)
+}
+
+fn skip<R: Read>(reader: &mut R, n: u64) -> Result<(), Error> {
+    io::copy(&mut reader.by_ref().take(n), &mut io::sink())?;
+    Ok(())
+}
+
+/// Measures the duration of a stream that can only be read forward, such as
+/// a network socket or a decompression stream.
+///
+/// [`from_file`] requires `Seek` because it jumps over skipped bytes with
+/// `seek(SeekFrom::Current(..))`. This variant instead consumes them by
+/// reading and discarding, so the crate can measure duration while
+/// streaming a download without buffering the whole file to disk first. It
+/// does not use the Xing/Info/VBRI fast path, which needs random access.
+///
+/// # Examples
+///
+/// ```
+/// use std::fs::File;
+/// use mp3_duration;
+///
+/// let mut file = File::open("test/source.mp3").unwrap();
+/// let duration = mp3_duration::from_reader(&mut file).unwrap();
+/// println!("File duration: {:?}", duration);
+/// ```
+pub fn from_reader<R>(reader: &mut R) -> Result<Duration, Error>
+    where R: Read
+{
+    scan_reader(reader, false)
+}
+
+/// Measures the duration of a forward-only stream, recovering from corrupt
+/// or unexpected data instead of aborting. See [`from_reader`] and
+/// [`from_file_lossy`] for details.
+///
+/// Unlike [`from_file_lossy`], a resync candidate here is accepted as soon
+/// as it decodes to a valid header, without also checking that the frame
+/// following it is consistent: confirming that would mean reading past it
+/// and then rewinding, which a forward-only stream can't do.
+///
+/// # Examples
+///
+/// ```
+/// use std::fs::File;
+/// use mp3_duration;
+///
+/// let mut file = File::open("test/source.mp3").unwrap();
+/// let duration = mp3_duration::from_reader_lossy(&mut file).unwrap();
+/// println!("File duration: {:?}", duration);
+/// ```
+pub fn from_reader_lossy<R>(reader: &mut R) -> Result<Duration, Error>
+    where R: Read
+{
+    scan_reader(reader, true)
+}
+
+fn scan_reader<R>(reader: &mut R, lossy: bool) -> Result<Duration, Error>
+    where R: Read
+{
+    let mut buffer = [0; 4];
+
+    let mut duration = Duration::from_secs(0);
+    // Set when `resync_reader` has left an accepted header in `buffer`.
+    let mut have_header = false;
+    loop {
+        if have_header {
+            have_header = false;
+        } else {
+            match reader.read_exact(&mut buffer[..]) {
+                Ok(_) => (),
+                Err(e) => {
+                    match e.kind() {
+                        std::io::ErrorKind::UnexpectedEof => break,
+                        _ => bail!(e),
+                    }
+                }
+            };
+        }
+
+        if skip_tag::<R, SkipAdvance>(reader, &mut buffer)? {
+            continue;
+        }
+
+        // MPEG frame
+        let header = (buffer[0] as u32) << 24 | (buffer[1] as u32) << 16 |
+                     (buffer[2] as u32) << 8 | buffer[3] as u32;
+        let is_mp3 = header >> 21 == 0x7FF;
+        if is_mp3 {
+            let info = decode_frame_header(header)?;
+
+            skip(reader, info.frame_length as u64)?;
+            duration = duration + Duration::new(0, info.frame_duration as u32);
+            continue;
+        }
+
+        if lossy {
+            match resync_reader(reader, &mut buffer)? {
+                true => {
+                    have_header = true;
+                    continue;
+                }
+                false => break,
+            }
+        }
+
+        bail!(MP3DurationError::UnexpectedFrame{ header: header });
+    }
+
+    Ok(duration)
+}
+
+// Forward-only counterpart to `try_resync`: slides the 4-byte window one
+// byte at a time until a header decodes successfully. It cannot also
+// validate the following frame's header like `try_resync` does, since
+// that would require reading ahead and then rewinding. On success,
+// `buffer` holds the accepted candidate header, ready to be decoded again
+// by the caller; on reaching end-of-stream it returns `Ok(false)`.
+fn resync_reader<R>(reader: &mut R, buffer: &mut [u8; 4]) -> Result<bool, Error>
+    where R: Read
+{
+    loop {
+        buffer[0] = buffer[1];
+        buffer[1] = buffer[2];
+        buffer[2] = buffer[3];
+        match reader.read_exact(&mut buffer[3..4]) {
+            Ok(_) => (),
+            Err(ref e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(false),
+            Err(e) => bail!(e),
+        }
+
+        let header = (buffer[0] as u32) << 24 | (buffer[1] as u32) << 16 |
+                     (buffer[2] as u32) << 8 | buffer[3] as u32;
+
+        if try_decode_frame_length(header).is_some() {
+            return Ok(true);
+        }
+    }
+}
+
+// Builds a synthetic frame header from its bit fields, exactly as
+// `decode_frame_header` expects to parse one. Used to construct small
+// in-memory streams for the tests below instead of relying on fixture
+// files.
+#[cfg(test)]
+fn encode_header(version: Version, layer: Layer, bitrate_index: u32, sampling_rate_index: u32,
+                  channel_mode: u32, emphasis: u32) -> u32 {
+    let version_bits = match version {
+        Version::Mpeg25 => 0,
+        Version::Mpeg2 => 2,
+        Version::Mpeg1 => 3,
+    };
+    let layer_bits = match layer {
+        Layer::NotDefined => 0,
+        Layer::Layer3 => 1,
+        Layer::Layer2 => 2,
+        Layer::Layer1 => 3,
+    };
+    let mut header = 0x7FFu32 << 21;
+    header |= version_bits << 19;
+    header |= layer_bits << 17;
+    header |= 1 << 16; // protection bit; unused by the decoder either way
+    header |= bitrate_index << 12;
+    header |= sampling_rate_index << 10;
+    header |= channel_mode << 6;
+    header |= emphasis;
+    header
+}
+
+// Builds a complete, well-formed frame (header + zeroed body sized to
+// match `decode_frame_header`'s own frame-length formula) so a stream of
+// these can be fed straight into `scan`/`scan_reader`.
+#[cfg(test)]
+fn build_frame(version: Version, layer: Layer, bitrate_index: u32, sampling_rate_index: u32,
+               channel_mode: u32) -> Vec<u8> {
+    let header = encode_header(version, layer, bitrate_index, sampling_rate_index, channel_mode, 0);
+    let bitrate = 1000 * BIT_RATES[version as usize][layer as usize][bitrate_index as usize];
+    let sampling_rate = SAMPLING_RATES[version as usize][sampling_rate_index as usize];
+    let num_samples = SAMPLES_PER_FRAME[version as usize][layer as usize];
+    let frame_length = if layer == Layer::Layer1 {
+        (12 * bitrate / sampling_rate) * 4 - 4
+    } else {
+        num_samples / 8 * bitrate / sampling_rate - 4
+    };
+    let mut bytes = header.to_be_bytes().to_vec();
+    bytes.resize(4 + frame_length as usize, 0);
+    bytes
+}
+
+#[cfg(test)]
+fn frame_duration_ns(version: Version, layer: Layer, sampling_rate_index: u32) -> u64 {
+    let sampling_rate = SAMPLING_RATES[version as usize][sampling_rate_index as usize] as u64;
+    let num_samples = SAMPLES_PER_FRAME[version as usize][layer as usize] as u64;
+    num_samples * 1_000_000_000 / sampling_rate
+}
+
+#[test]
+fn xing_vbr_fast_path() {
+    use std::io::Cursor;
+
+    // Stereo Mpeg1 Layer3 at 128kbps, whose frame body is large enough to
+    // hold the Xing tag right after the 32-byte side information.
+    let mut frame = build_frame(Version::Mpeg1, Layer::Layer3, 9, 0, 0);
+    let side_info_size = 32;
+    let frame_count: u32 = 100;
+    let mut tag = Vec::new();
+    tag.extend_from_slice(b"Xing");
+    tag.extend_from_slice(&[0, 0, 0, 1]); // flags: frame count field present
+    tag.extend_from_slice(&frame_count.to_be_bytes());
+    assert!(4 + side_info_size + tag.len() <= frame.len());
+    frame[4 + side_info_size..4 + side_info_size + tag.len()].copy_from_slice(&tag);
+
+    let mut cursor = Cursor::new(frame);
+    let metadata = from_file_ext(&mut cursor).unwrap();
+    assert_eq!(BitrateMode::VariableBitrate, metadata.bitrate_mode);
+    assert_eq!(frame_count, metadata.frame_count);
+    let expected_ns = frame_duration_ns(Version::Mpeg1, Layer::Layer3, 0) * frame_count as u64;
+    assert_eq!(Duration::new(expected_ns / 1_000_000_000, (expected_ns % 1_000_000_000) as u32),
+               metadata.duration);
+}
+
+#[test]
+fn resync_recovers_after_a_stray_byte() {
+    use std::io::Cursor;
+
+    let frame1 = build_frame(Version::Mpeg1, Layer::Layer3, 9, 0, 0);
+    let frame2 = build_frame(Version::Mpeg1, Layer::Layer3, 9, 0, 0);
+
+    // A single stray byte ahead of the real audio: resync must start its
+    // slide right after it, not after the whole rejected 4-byte word.
+    let mut data = vec![0xAB];
+    data.extend_from_slice(&frame1);
+    data.extend_from_slice(&frame2);
+
+    let mut cursor = Cursor::new(data);
+    let duration = from_file_lossy(&mut cursor).unwrap();
+
+    let frame_ns = frame_duration_ns(Version::Mpeg1, Layer::Layer3, 0) as u32;
+    assert_eq!(Duration::new(0, frame_ns) * 2, duration);
+}
+
+#[test]
+fn ext_metadata_reports_constant_bitrate() {
+    use std::io::Cursor;
+
+    let frame1 = build_frame(Version::Mpeg1, Layer::Layer3, 9, 0, 0);
+    let frame2 = build_frame(Version::Mpeg1, Layer::Layer3, 9, 0, 0);
+    let mut data = frame1.clone();
+    data.extend_from_slice(&frame2);
+
+    let mut cursor = Cursor::new(data);
+    let metadata = from_file_ext(&mut cursor).unwrap();
+
+    assert_eq!(BitrateMode::ConstantBitrate, metadata.bitrate_mode);
+    assert_eq!(Version::Mpeg1, metadata.version);
+    assert_eq!(Layer::Layer3, metadata.layer);
+    assert_eq!(ChannelMode::Stereo, metadata.channel_mode);
+    assert_eq!(2, metadata.frame_count);
+    assert_eq!(2 * 1152, metadata.sample_count);
+    assert!((metadata.average_bitrate as i64 - 128000).abs() < 50);
+}
+
+#[test]
+fn info_tag_reports_constant_bitrate() {
+    use std::io::Cursor;
+
+    // LAME writes the same Xing-tag layout under the name "Info" to mark a
+    // CBR/ABR-encoded stream instead of a VBR one.
+    let mut frame = build_frame(Version::Mpeg1, Layer::Layer3, 9, 0, 0);
+    let side_info_size = 32;
+    let frame_count: u32 = 50;
+    let mut tag = Vec::new();
+    tag.extend_from_slice(b"Info");
+    tag.extend_from_slice(&[0, 0, 0, 1]); // flags: frame count field present
+    tag.extend_from_slice(&frame_count.to_be_bytes());
+    assert!(4 + side_info_size + tag.len() <= frame.len());
+    frame[4 + side_info_size..4 + side_info_size + tag.len()].copy_from_slice(&tag);
+
+    let mut cursor = Cursor::new(frame);
+    let metadata = from_file_ext(&mut cursor).unwrap();
+    assert_eq!(BitrateMode::ConstantBitrate, metadata.bitrate_mode);
+    assert_eq!(frame_count, metadata.frame_count);
+}
+
+#[test]
+fn vbri_tag_reports_variable_bitrate() {
+    use std::io::Cursor;
+
+    let mut frame = build_frame(Version::Mpeg1, Layer::Layer3, 9, 0, 0);
+    let frame_count: u32 = 77;
+    let mut tag = Vec::new();
+    tag.extend_from_slice(b"VBRI");
+    tag.extend_from_slice(&[0u8; 10]); // opaque VBRI header fields, unused here
+    tag.extend_from_slice(&frame_count.to_be_bytes());
+    let offset = 32; // VBRI tag sits at a fixed offset, regardless of channel mode
+    assert!(4 + offset + tag.len() <= frame.len());
+    frame[4 + offset..4 + offset + tag.len()].copy_from_slice(&tag);
+
+    let mut cursor = Cursor::new(frame);
+    let metadata = from_file_ext(&mut cursor).unwrap();
+    assert_eq!(BitrateMode::VariableBitrate, metadata.bitrate_mode);
+    assert_eq!(frame_count, metadata.frame_count);
+}
+
+#[test]
+fn layer1_frame_length_round_trips() {
+    use std::io::Cursor;
+
+    let frame1 = build_frame(Version::Mpeg1, Layer::Layer1, 5, 0, 0);
+    let frame2 = build_frame(Version::Mpeg1, Layer::Layer1, 5, 0, 0);
+    let mut data = frame1.clone();
+    data.extend_from_slice(&frame2);
+
+    let mut cursor = Cursor::new(data);
+    let duration = from_file(&mut cursor).unwrap();
+
+    let frame_ns = frame_duration_ns(Version::Mpeg1, Layer::Layer1, 0) as u32;
+    assert_eq!(Duration::new(0, frame_ns) * 2, duration);
+}
+
+#[test]
+fn rejects_reserved_emphasis_bits() {
+    use std::io::Cursor;
+
+    let header = encode_header(Version::Mpeg1, Layer::Layer3, 9, 0, 0, 0b10);
+    let mut data = header.to_be_bytes().to_vec();
+    data.resize(4 + 414, 0);
+
+    let mut cursor = Cursor::new(data);
+    assert!(from_file(&mut cursor).is_err());
+}
+
+#[test]
+fn skips_apev2_trailer_without_overrunning() {
+    use std::io::Cursor;
+
+    let frame1 = build_frame(Version::Mpeg1, Layer::Layer3, 9, 0, 0);
+    let frame2 = build_frame(Version::Mpeg1, Layer::Layer3, 9, 0, 0);
+
+    let mut remaining_header = [0u8; 24];
+    // `tag_size` covers only the 32-byte footer here (no tag items).
+    remaining_header[4..8].copy_from_slice(&32u32.to_le_bytes());
+
+    let mut data = frame1.clone();
+    data.extend_from_slice(b"APETAGEX");
+    data.extend_from_slice(&remaining_header);
+    data.extend_from_slice(&[0u8; 32]); // the footer itself
+    data.extend_from_slice(&frame2);
+
+    let mut cursor = Cursor::new(data);
+    let duration = from_file(&mut cursor).unwrap();
+
+    let frame_ns = frame_duration_ns(Version::Mpeg1, Layer::Layer3, 0) as u32;
+    assert_eq!(Duration::new(0, frame_ns) * 2, duration);
+}
+
+#[test]
+fn skips_lyrics3v2_trailer_via_begin_marker() {
+    use std::io::Cursor;
+
+    let frame1 = build_frame(Version::Mpeg1, Layer::Layer3, 9, 0, 0);
+    let frame2 = build_frame(Version::Mpeg1, Layer::Layer3, 9, 0, 0);
+    let content = b"TT000012Some Song";
+
+    let mut data = frame1.clone();
+    data.extend_from_slice(b"LYRICSBEGIN");
+    data.extend_from_slice(format!("{:06}", content.len()).as_bytes());
+    data.extend_from_slice(content);
+    data.extend_from_slice(b"LYRICS200");
+    data.extend_from_slice(&frame2);
+
+    let mut cursor = Cursor::new(data);
+    let duration = from_file(&mut cursor).unwrap();
+
+    let frame_ns = frame_duration_ns(Version::Mpeg1, Layer::Layer3, 0) as u32;
+    assert_eq!(Duration::new(0, frame_ns) * 2, duration);
+}
+
+#[test]
+fn from_reader_parses_forward_only_stream() {
+    let frame1 = build_frame(Version::Mpeg1, Layer::Layer3, 9, 0, 0);
+    let frame2 = build_frame(Version::Mpeg1, Layer::Layer3, 9, 0, 0);
+    let mut data = frame1.clone();
+    data.extend_from_slice(&frame2);
+
+    let mut reader: &[u8] = &data;
+    let duration = from_reader(&mut reader).unwrap();
+
+    let frame_ns = frame_duration_ns(Version::Mpeg1, Layer::Layer3, 0) as u32;
+    assert_eq!(Duration::new(0, frame_ns) * 2, duration);
+}
+
 #[test]
 fn constant_bitrate_320() {
     let path = Path::new("test/CBR320.mp3");